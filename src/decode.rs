@@ -0,0 +1,445 @@
+//! Demodulation of AFSK-encoded SAME/EAS headers back into a [`Header`].
+//!
+//! This is the inverse of [`Header::render`]: it takes raw audio samples,
+//! locks onto the 0xAB preamble using per-bit Goertzel tone discrimination,
+//! and walks the `ZCZC-…` framing grammar to recover the header fields. Since
+//! a real transmission repeats the header three times, the three bursts are
+//! decoded independently and reconciled: bursts that framed to the same
+//! length are corrected with a majority vote per byte position, and a burst
+//! that failed to frame (or desynced to a different length) is simply
+//! outvoted rather than aborting the decode. See [`reconcile_bursts`] for
+//! the precise guarantee.
+
+use crate::{
+    AfskBit, AfskByte, BIT_SECONDS, Header, LocationCodeError, MARK_CYCLES, SPACE_CYCLES,
+    bit_window_len,
+    grammar::{self, FieldError, FramingError},
+    preamble,
+};
+
+/// Errors that can occur while demodulating a SAME/EAS header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Fewer samples were provided than a single bit window requires, or the
+    /// samples ended before a complete header burst could be read.
+    InsufficientSamples,
+    /// No 16-byte 0xAB preamble could be found, so a bit clock could not be
+    /// established.
+    LossOfLock,
+    /// The decoded bytes did not start with the `ZCZC-` framing marker, or a
+    /// later delimiter or fixed-width field was missing or malformed.
+    BadFraming,
+    /// More than 31 location codes were present in a single header.
+    TooManyLocationCodes,
+    /// The 3-letter originator code did not match any known [`OriginatorCode`].
+    UnknownOriginatorCode([u8; 3]),
+    /// The day-of-year/hour/minute field did not form a valid date.
+    InvalidTimeOfIssue,
+    /// A location code was not a valid 6-digit PSSCCC code.
+    InvalidLocationCode(LocationCodeError),
+    /// The three redundant header bursts couldn't settle on a 2-of-3 (or
+    /// 3-of-3) length majority to reconcile, so no majority vote could be
+    /// taken.
+    BurstLengthMismatch,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InsufficientSamples => write!(f, "not enough samples to decode a header"),
+            Self::LossOfLock => write!(f, "could not lock onto the AFSK preamble"),
+            Self::BadFraming => write!(f, "malformed ZCZC- header framing"),
+            Self::TooManyLocationCodes => write!(f, "more than 31 location codes in header"),
+            Self::UnknownOriginatorCode(code) => {
+                write!(f, "unknown originator code {:?}", code)
+            }
+            Self::InvalidTimeOfIssue => write!(f, "invalid day-of-year/hour/minute field"),
+            Self::InvalidLocationCode(err) => write!(f, "invalid location code: {err:?}"),
+            Self::BurstLengthMismatch => {
+                write!(f, "the three header bursts could not reach a length majority")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<FramingError> for DecodeError {
+    fn from(err: FramingError) -> Self {
+        match err {
+            FramingError::BadFraming => Self::BadFraming,
+            FramingError::TooManyLocationCodes => Self::TooManyLocationCodes,
+        }
+    }
+}
+
+impl From<FieldError> for DecodeError {
+    fn from(err: FieldError) -> Self {
+        match err {
+            FieldError::UnknownOriginatorCode(code) => Self::UnknownOriginatorCode(code),
+            FieldError::InvalidLocationCode(err) => Self::InvalidLocationCode(err),
+            FieldError::InvalidTimeOfIssue => Self::InvalidTimeOfIssue,
+        }
+    }
+}
+
+/// Demodulate `samples` (captured at `sample_rate` Hz) back into a [`Header`].
+///
+/// `samples` should begin at or before the first of the three redundant
+/// header bursts emitted by [`crate::EasWarning::construct`]; trailing audio
+/// (attention tone, voice message, end-of-message markers) is ignored.
+///
+/// The header's time of issue is only transmitted as a day-of-year/hour/minute
+/// triple (no year), so the decoded [`chrono::DateTime`] assumes the current
+/// UTC year.
+///
+/// Location codes are decoded as [`LocationCode::Pssccc`], since the wire
+/// format does not distinguish US PSSCCC codes from Canadian CLC codes.
+///
+/// Each of the three redundant bursts is framed independently, so a burst
+/// whose own `ZCZC-…` framing desyncs (a corrupted delimiter, a lost
+/// preamble lock) produces bytes of a different length than the other two
+/// and is outvoted wholesale rather than aborting the decode; see
+/// [`reconcile_bursts`] for exactly what this guarantees.
+pub fn decode(samples: &[f32], sample_rate: usize) -> Result<Header, DecodeError> {
+    let window_len = bit_window_len(sample_rate);
+    if window_len == 0 || samples.len() < window_len {
+        return Err(DecodeError::InsufficientSamples);
+    }
+
+    let mut cursor = 0;
+    let mut attempts = Vec::with_capacity(3);
+    for _ in 0..3 {
+        let attempt = decode_one_burst(&samples[cursor..], sample_rate);
+        cursor += match &attempt {
+            Ok((_, consumed)) => *consumed,
+            // A burst that failed to frame didn't report how much of the
+            // slice it consumed; fall back to one bit window so the search
+            // for the next burst's preamble still makes forward progress.
+            Err(_) => window_len,
+        };
+        attempts.push(attempt);
+    }
+
+    let voted = reconcile_bursts(attempts)?;
+    let raw = grammar::parse_raw(&voted)?;
+    Ok(grammar::assemble(raw)?)
+}
+
+fn goertzel_power(window: &[f32], sample_rate: usize, target_freq: f32) -> f32 {
+    let n = window.len() as f32;
+    let k = (n * target_freq / sample_rate as f32).round();
+    let omega = 2.0 * std::f32::consts::PI * k / n;
+    let coeff = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0f32, 0.0f32);
+    for &x in window {
+        let s = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    s1 * s1 + s2 * s2 - coeff * s1 * s2
+}
+
+/// Discriminate a single bit window as [`AfskBit::Mark`] or [`AfskBit::Space`]
+/// by comparing Goertzel tone energy at the mark and space frequencies.
+fn discriminate_bit(window: &[f32], sample_rate: usize) -> AfskBit {
+    let mark_power = goertzel_power(window, sample_rate, MARK_CYCLES / BIT_SECONDS);
+    let space_power = goertzel_power(window, sample_rate, SPACE_CYCLES / BIT_SECONDS);
+    (mark_power > space_power).into()
+}
+
+/// Try every phase offset within one bit window and keep the one whose first
+/// 128 bits (16 preamble bytes of 0xAB) best match the expected pattern.
+fn find_preamble_lock(samples: &[f32], sample_rate: usize, window_len: usize) -> Option<usize> {
+    let expected: Vec<AfskBit> = preamble().iter().flat_map(|byte| byte.bits).collect();
+    let preamble_len = expected.len();
+
+    let step = (window_len / 8).max(1);
+    let mut best: Option<(usize, usize)> = None;
+
+    let mut offset = 0;
+    while offset + preamble_len * window_len <= samples.len() {
+        let matches = (0..preamble_len)
+            .map(|i| {
+                let start = offset + i * window_len;
+                discriminate_bit(&samples[start..start + window_len], sample_rate)
+            })
+            .zip(expected.iter())
+            .filter(|(actual, expected)| actual == *expected)
+            .count();
+
+        let is_better = match best {
+            Some((_, best_matches)) => matches > best_matches,
+            None => true,
+        };
+        if is_better {
+            best = Some((offset, matches));
+        }
+        offset += step;
+    }
+
+    best.filter(|(_, matches)| *matches * 4 >= preamble_len * 3)
+        .map(|(offset, _)| offset)
+}
+
+/// Decode a single header burst from the start of `samples`, returning its
+/// raw ASCII bytes (the `ZCZC-…-` line, excluding the preamble) and the
+/// number of samples consumed, including the leading preamble and framing.
+fn decode_one_burst(samples: &[f32], sample_rate: usize) -> Result<(Vec<u8>, usize), DecodeError> {
+    let window_len = bit_window_len(sample_rate);
+    let lock_offset =
+        find_preamble_lock(samples, sample_rate, window_len).ok_or(DecodeError::LossOfLock)?;
+
+    let mut position = lock_offset + 16 * 8 * window_len;
+    let mut read_byte = |position: &mut usize| -> Result<u8, DecodeError> {
+        if *position + 8 * window_len > samples.len() {
+            return Err(DecodeError::InsufficientSamples);
+        }
+        let bits: [AfskBit; 8] = std::array::from_fn(|i| {
+            let start = *position + i * window_len;
+            discriminate_bit(&samples[start..start + window_len], sample_rate)
+        });
+        *position += 8 * window_len;
+        Ok(AfskByte { bits }.into())
+    };
+
+    let mut bytes = Vec::new();
+
+    for expected in b"ZCZC-" {
+        let byte = read_byte(&mut position)?;
+        if byte != *expected {
+            return Err(DecodeError::BadFraming);
+        }
+        bytes.push(byte);
+    }
+
+    for _ in 0..3 {
+        bytes.push(read_byte(&mut position)?);
+    }
+    if read_byte(&mut position)? != b'-' {
+        return Err(DecodeError::BadFraming);
+    }
+    bytes.push(b'-');
+    for _ in 0..3 {
+        bytes.push(read_byte(&mut position)?);
+    }
+
+    let mut location_code_count = 0;
+    loop {
+        let delimiter = read_byte(&mut position)?;
+        bytes.push(delimiter);
+        match delimiter {
+            b'+' => break,
+            b'-' => {
+                location_code_count += 1;
+                if location_code_count > 31 {
+                    return Err(DecodeError::TooManyLocationCodes);
+                }
+                for _ in 0..6 {
+                    bytes.push(read_byte(&mut position)?);
+                }
+            }
+            _ => return Err(DecodeError::BadFraming),
+        }
+    }
+
+    for _ in 0..4 {
+        bytes.push(read_byte(&mut position)?);
+    }
+    if read_byte(&mut position)? != b'-' {
+        return Err(DecodeError::BadFraming);
+    }
+    bytes.push(b'-');
+    for _ in 0..7 {
+        bytes.push(read_byte(&mut position)?);
+    }
+    if read_byte(&mut position)? != b'-' {
+        return Err(DecodeError::BadFraming);
+    }
+    bytes.push(b'-');
+    for _ in 0..8 {
+        bytes.push(read_byte(&mut position)?);
+    }
+    if read_byte(&mut position)? != b'-' {
+        return Err(DecodeError::BadFraming);
+    }
+    bytes.push(b'-');
+
+    Ok((bytes, position))
+}
+
+/// Reconcile the outcomes of decoding the three redundant header bursts.
+///
+/// Each burst is framed independently by [`decode_one_burst`], so the actual
+/// robustness guarantee is two-tiered:
+///
+/// - Corruption confined to the fixed-width payload bytes of a burst that
+///   still framed correctly (same length as the others) is corrected by a
+///   per-byte majority vote in [`majority_vote`].
+/// - Corruption severe enough to desync a burst's own framing (a garbled
+///   `-`/`+` delimiter, a lost preamble lock, …) makes that burst fail to
+///   decode, or decode to a different byte length than the other two; such
+///   a burst is discarded wholesale in favor of whichever length the
+///   majority of the three bursts agree on, rather than aborting the whole
+///   decode. If the three bursts can't settle on either a 2-of-3 length
+///   majority or a single surviving burst, decoding fails with
+///   [`DecodeError::BurstLengthMismatch`].
+fn reconcile_bursts(
+    attempts: Vec<Result<(Vec<u8>, usize), DecodeError>>,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut first_err = None;
+    let mut oks: Vec<Vec<u8>> = Vec::new();
+    for attempt in attempts {
+        match attempt {
+            Ok((bytes, _)) => oks.push(bytes),
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    let total = oks.len();
+    if total == 0 {
+        return Err(first_err.unwrap_or(DecodeError::LossOfLock));
+    }
+
+    let mut by_len: Vec<(usize, Vec<Vec<u8>>)> = Vec::new();
+    for bytes in oks {
+        match by_len.iter_mut().find(|(len, _)| *len == bytes.len()) {
+            Some((_, group)) => group.push(bytes),
+            None => by_len.push((bytes.len(), vec![bytes])),
+        }
+    }
+
+    let winner = by_len.into_iter().max_by_key(|(_, group)| group.len());
+    match winner {
+        // A lone successfully-framed burst, or a strict 2-of-3 (or 3-of-3)
+        // length majority: vote within it (trivial if it has one member).
+        Some((_, group)) if group.len() > total / 2 => majority_vote(&group),
+        _ => Err(DecodeError::BurstLengthMismatch),
+    }
+}
+
+/// Reconcile same-length header bursts with a per-position majority vote,
+/// correcting bit corruption confined to their fixed-width payload bytes.
+///
+/// Callers must only pass bursts of identical length — see
+/// [`reconcile_bursts`], which groups the raw per-burst decode attempts by
+/// length before calling this.
+fn majority_vote(bursts: &[Vec<u8>]) -> Result<Vec<u8>, DecodeError> {
+    let len = bursts[0].len();
+
+    Ok((0..len)
+        .map(|i| {
+            let mut counts = [0usize; 256];
+            for burst in bursts {
+                counts[burst[i] as usize] += 1;
+            }
+            counts
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, count)| **count)
+                .map(|(byte, _)| byte as u8)
+                .unwrap()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+
+    use super::decode;
+    use crate::{EasWarning, EventCode, Header, LocationCode, OriginatorCode};
+
+    #[test]
+    fn round_trip() {
+        let sample_rate = 22_050;
+        let header = Header::builder()
+            .time_of_issue(Utc::now())
+            .event_code(EventCode::Rwt)
+            .purge_time(*b"0015")
+            .callsign(*b"WDAF/FM ")
+            .location_codes(vec![LocationCode::pssccc(*b"048077").unwrap()])
+            .unwrap()
+            .originator_code(OriginatorCode::Civ)
+            .build();
+
+        let warning = EasWarning::new(header, false);
+        let samples = warning.construct(sample_rate, None, false);
+
+        let decoded = decode(&samples, sample_rate).expect("should decode");
+        assert_eq!(decoded.event_code, EventCode::Rwt);
+        assert_eq!(decoded.purge_time, *b"0015");
+        assert_eq!(
+            decoded.location_codes,
+            vec![LocationCode::pssccc(*b"048077").unwrap()]
+        );
+        assert_eq!(decoded.callsign, *b"WDAF/FM ");
+    }
+
+    /// 44.1 kHz is the most common audio sample rate, and the one where
+    /// `floor(sample_rate * BIT_SECONDS)` and `round(...)` disagree (84 vs
+    /// 85 samples/bit). A decode bit window computed with `round` drifts
+    /// from the modulator's `floor`-based window across the 128-bit
+    /// preamble and never reaches lock.
+    #[test]
+    fn round_trip_44100() {
+        let sample_rate = 44_100;
+        let header = Header::builder()
+            .time_of_issue(Utc::now())
+            .event_code(EventCode::Rwt)
+            .purge_time(*b"0015")
+            .callsign(*b"WDAF/FM ")
+            .location_codes(vec![LocationCode::pssccc(*b"048077").unwrap()])
+            .unwrap()
+            .originator_code(OriginatorCode::Civ)
+            .build();
+
+        let warning = EasWarning::new(header, false);
+        let samples = warning.construct(sample_rate, None, false);
+
+        let decoded = decode(&samples, sample_rate).expect("should decode");
+        assert_eq!(decoded.event_code, EventCode::Rwt);
+        assert_eq!(decoded.purge_time, *b"0015");
+        assert_eq!(
+            decoded.location_codes,
+            vec![LocationCode::pssccc(*b"048077").unwrap()]
+        );
+        assert_eq!(decoded.callsign, *b"WDAF/FM ");
+    }
+
+    /// A burst corrupted badly enough to desync its own framing (simulated
+    /// here by silencing one of the three redundant bursts outright, which
+    /// kills its preamble lock) must not abort the whole decode: the other
+    /// two bursts still agree and should be used via [`super::reconcile_bursts`].
+    #[test]
+    fn decode_tolerates_one_unlockable_burst() {
+        let sample_rate = 22_050;
+        let header = Header::builder()
+            .time_of_issue(Utc::now())
+            .event_code(EventCode::Rwt)
+            .purge_time(*b"0015")
+            .callsign(*b"WDAF/FM ")
+            .location_codes(vec![LocationCode::pssccc(*b"048077").unwrap()])
+            .unwrap()
+            .originator_code(OriginatorCode::Civ)
+            .build();
+        let same_string_len = header.to_same_string().unwrap().len();
+
+        let warning = EasWarning::new(header, false);
+        let mut samples = warning.construct(sample_rate, None, false);
+
+        let silence_samples = (sample_rate as f32).floor() as usize;
+        // 16 preamble bytes plus the `ZCZC-…` bytes, 8 bits each.
+        let burst_samples = (16 + same_string_len) * 8 * crate::bit_window_len(sample_rate);
+        let second_burst_start = burst_samples + silence_samples;
+        for sample in &mut samples[second_burst_start..second_burst_start + burst_samples] {
+            *sample = 0.0;
+        }
+
+        let decoded =
+            decode(&samples, sample_rate).expect("the other two bursts should still agree");
+        assert_eq!(decoded.event_code, EventCode::Rwt);
+        assert_eq!(decoded.callsign, *b"WDAF/FM ");
+    }
+}
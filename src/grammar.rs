@@ -0,0 +1,190 @@
+//! Parsing for the `ZCZC-…` SAME/EAS header grammar, shared by the AFSK
+//! demodulator ([`crate::decode`]) and the plain-text parser
+//! ([`crate::same_string`]).
+//!
+//! This mirrors a byte-view decoder with a read offset: [`Decoder`] only
+//! knows how to pull fixed-size chunks off the front of a byte slice, while
+//! [`parse_raw`] and [`assemble`] layer the header's actual framing and
+//! field semantics on top.
+
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
+
+use crate::{EventCode, Header, LocationCode, LocationCodeError, OriginatorCode};
+
+/// A byte-view decoder that consumes its slice from the front as it reads.
+pub(crate) struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Take the next `n` bytes, or `None` if fewer than `n` remain.
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    /// Take the next single byte.
+    pub(crate) fn take_byte(&mut self) -> Option<u8> {
+        self.take(1).map(|byte| byte[0])
+    }
+}
+
+/// The header fields as raw wire bytes, with framing validated but no field
+/// semantics applied yet.
+pub(crate) struct RawHeader {
+    pub(crate) originator_code: [u8; 3],
+    pub(crate) event_code: [u8; 3],
+    pub(crate) location_codes: Vec<[u8; 6]>,
+    pub(crate) purge_time: [u8; 4],
+    pub(crate) datetime: [u8; 7],
+    pub(crate) callsign: [u8; 8],
+}
+
+/// Errors in the structural framing of a `ZCZC-…` header, before any field
+/// is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FramingError {
+    /// A delimiter or fixed-width field was missing or malformed.
+    BadFraming,
+    /// More than 31 location codes were present.
+    TooManyLocationCodes,
+}
+
+/// Walk the `ZCZC-ORG-EEE(-PSSCCC)*+TTTT-JJJHHMM-CALLSIGN-` grammar,
+/// returning the raw field bytes.
+pub(crate) fn parse_raw(bytes: &[u8]) -> Result<RawHeader, FramingError> {
+    let mut decoder = Decoder::new(bytes);
+
+    if decoder.take(5) != Some(b"ZCZC-") {
+        return Err(FramingError::BadFraming);
+    }
+
+    let originator_code: [u8; 3] = decoder
+        .take(3)
+        .ok_or(FramingError::BadFraming)?
+        .try_into()
+        .unwrap();
+    if decoder.take_byte() != Some(b'-') {
+        return Err(FramingError::BadFraming);
+    }
+    let event_code: [u8; 3] = decoder
+        .take(3)
+        .ok_or(FramingError::BadFraming)?
+        .try_into()
+        .unwrap();
+
+    let mut location_codes = Vec::new();
+    loop {
+        match decoder.take_byte().ok_or(FramingError::BadFraming)? {
+            b'+' => break,
+            b'-' => {
+                if location_codes.len() >= 31 {
+                    return Err(FramingError::TooManyLocationCodes);
+                }
+                let code: [u8; 6] = decoder
+                    .take(6)
+                    .ok_or(FramingError::BadFraming)?
+                    .try_into()
+                    .unwrap();
+                location_codes.push(code);
+            }
+            _ => return Err(FramingError::BadFraming),
+        }
+    }
+
+    let purge_time: [u8; 4] = decoder
+        .take(4)
+        .ok_or(FramingError::BadFraming)?
+        .try_into()
+        .unwrap();
+    if decoder.take_byte() != Some(b'-') {
+        return Err(FramingError::BadFraming);
+    }
+    let datetime: [u8; 7] = decoder
+        .take(7)
+        .ok_or(FramingError::BadFraming)?
+        .try_into()
+        .unwrap();
+    if decoder.take_byte() != Some(b'-') {
+        return Err(FramingError::BadFraming);
+    }
+    let callsign: [u8; 8] = decoder
+        .take(8)
+        .ok_or(FramingError::BadFraming)?
+        .try_into()
+        .unwrap();
+    if decoder.take_byte() != Some(b'-') {
+        return Err(FramingError::BadFraming);
+    }
+
+    Ok(RawHeader {
+        originator_code,
+        event_code,
+        location_codes,
+        purge_time,
+        datetime,
+        callsign,
+    })
+}
+
+/// Errors giving raw header bytes their typed meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldError {
+    /// The 3-letter originator code did not match any known [`OriginatorCode`].
+    UnknownOriginatorCode([u8; 3]),
+    /// A location code was not a valid 6-digit PSSCCC code.
+    InvalidLocationCode(LocationCodeError),
+    /// The day-of-year/hour/minute field did not form a valid date.
+    InvalidTimeOfIssue,
+}
+
+/// Give a [`RawHeader`]'s bytes their typed meaning, producing a [`Header`].
+///
+/// Location codes are assembled as [`LocationCode::Pssccc`], since the wire
+/// format does not distinguish US PSSCCC codes from Canadian CLC codes.
+/// The time of issue is only transmitted as a day-of-year/hour/minute triple
+/// (no year), so the resulting [`chrono::DateTime`] assumes the current UTC
+/// year.
+pub(crate) fn assemble(raw: RawHeader) -> Result<Header, FieldError> {
+    let originator_code = OriginatorCode::try_from(raw.originator_code)
+        .map_err(FieldError::UnknownOriginatorCode)?;
+    let event_code = EventCode::from(raw.event_code);
+    let location_codes = raw
+        .location_codes
+        .into_iter()
+        .map(|code| LocationCode::pssccc(code).map_err(FieldError::InvalidLocationCode))
+        .collect::<Result<Vec<_>, _>>()?;
+    let time_of_issue = parse_time_of_issue(&raw.datetime)?;
+    let callsign = raw
+        .callsign
+        .map(|byte| if byte == b'\\' { b'-' } else { byte });
+
+    Ok(Header {
+        originator_code,
+        event_code,
+        location_codes,
+        purge_time: raw.purge_time,
+        time_of_issue,
+        callsign,
+    })
+}
+
+fn parse_time_of_issue(field: &[u8; 7]) -> Result<chrono::DateTime<Utc>, FieldError> {
+    let field = std::str::from_utf8(field).map_err(|_| FieldError::InvalidTimeOfIssue)?;
+    let day_of_year: u32 = field[0..3].parse().map_err(|_| FieldError::InvalidTimeOfIssue)?;
+    let hour: u32 = field[3..5].parse().map_err(|_| FieldError::InvalidTimeOfIssue)?;
+    let minute: u32 = field[5..7].parse().map_err(|_| FieldError::InvalidTimeOfIssue)?;
+
+    let year = Utc::now().year();
+    let date = NaiveDate::from_yo_opt(year, day_of_year).ok_or(FieldError::InvalidTimeOfIssue)?;
+    let time = NaiveTime::from_hms_opt(hour, minute, 0).ok_or(FieldError::InvalidTimeOfIssue)?;
+    Ok(Utc.from_utc_datetime(&date.and_time(time)))
+}
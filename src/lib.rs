@@ -2,6 +2,35 @@ use std::{array, f32::consts::PI};
 
 use chrono::{DateTime, Utc};
 
+pub mod decode;
+mod grammar;
+pub mod output;
+#[cfg(feature = "playback")]
+pub mod playback;
+pub mod same_string;
+pub mod sink;
+
+use sink::SampleSink;
+
+/// Duration of a single AFSK bit, in seconds. Shared by the modulator and the
+/// demodulator so the two stay in lockstep.
+const BIT_SECONDS: f32 = 1.92 / 1000.0;
+
+/// Number of samples [`MultiSineWave::generate_samples`] renders for a single
+/// AFSK bit at `sample_rate` Hz.
+///
+/// [`crate::decode`] derives its demodulation bit window from this same
+/// `floor`, not a `round`, since a 1-sample/bit disagreement between the
+/// modulator and demodulator accumulates linearly across the header and
+/// breaks preamble lock at common sample rates (e.g. 44.1 kHz).
+pub(crate) fn bit_window_len(sample_rate: usize) -> usize {
+    (sample_rate as f32 * BIT_SECONDS).floor() as usize
+}
+/// Number of tone cycles in a [`AfskBit::Mark`] bit window.
+const MARK_CYCLES: f32 = 4.0;
+/// Number of tone cycles in a [`AfskBit::Space`] bit window.
+const SPACE_CYCLES: f32 = 3.0;
+
 pub struct EasWarning {
     header: Header,
     attention_signal: AttentionSignal,
@@ -28,6 +57,27 @@ impl EasWarning {
         message: Option<Vec<f32>>,
         critical: bool,
     ) -> Vec<f32> {
+        let mut samples = Vec::new();
+        self.construct_to(sample_rate, message, critical, &mut samples)
+            .unwrap();
+        samples
+    }
+
+    /// Render this warning section-by-section, pushing samples to `sink` as
+    /// each section is generated instead of materializing the whole
+    /// broadcast into one buffer first.
+    ///
+    /// This lets a [`sink`](crate::sink) stream directly to a transport, such
+    /// as a [`sink::WriteSink`] or [`sink::TcpSink`], without the rendered
+    /// alert ever being collected into one buffer. The `message` samples
+    /// themselves are still held in memory, and copied once while rendered.
+    pub fn construct_to<S: SampleSink>(
+        &self,
+        sample_rate: usize,
+        message: Option<Vec<f32>>,
+        critical: bool,
+        sink: &mut S,
+    ) -> Result<(), S::Error> {
         use Section::*;
         let mut sections = vec![];
 
@@ -56,32 +106,28 @@ impl EasWarning {
         sections.push(Silence(1.0));
         sections.push(AfskBytes(eom));
 
-        Self::render(&sections, sample_rate)
-    }
-
-    fn render(sections: &[Section], sample_rate: usize) -> Vec<f32> {
-        sections
-            .iter()
-            .flat_map(|section| section.render(sample_rate))
-            .collect()
+        for section in &sections {
+            sink.push(&section.render(sample_rate))?;
+        }
+        Ok(())
     }
 }
 
 #[derive(bon::Builder)]
 pub struct Header {
     originator_code: OriginatorCode,
-    event_code: [u8; 3],
+    event_code: EventCode,
     /// In Canada, these are Canadian Location Codes (CLC). In the US, a specific format is followed (PSSCCC)
     ///
     /// Maximum of 31 codes per message.
-    #[builder(with = |codes: Vec<[u8; 6]>| -> Result<_, ()> {
+    #[builder(with = |codes: Vec<LocationCode>| -> Result<_, LocationCodeError> {
         if codes.len() <= 31 {
             Ok(codes)
         } else {
-            Err(())
+            Err(LocationCodeError::TooManyCodes)
         }
     })]
-    location_codes: Vec<[u8; 6]>,
+    location_codes: Vec<LocationCode>,
     purge_time: [u8; 4],
     time_of_issue: DateTime<Utc>,
     /// Must be 8 characters long.
@@ -94,38 +140,206 @@ pub struct Header {
 }
 
 impl Header {
-    fn render(&self) -> Vec<AfskByte> {
+    /// Render the `ZCZC-…` protocol string for this header as plain ASCII
+    /// bytes, with no preamble or AFSK modulation. Shared by [`Self::render`]
+    /// (which wraps it in tone) and [`Self::to_same_string`].
+    fn same_string_bytes(&self) -> Vec<u8> {
         let formatted_datetime = self.time_of_issue.format("%j%H%M").to_string();
         let stripped_callsign = self
             .callsign
             .map(|char| if char == b'-' { b'\\' } else { char });
 
-        let mut header = vec![preamble().to_vec()];
-
-        header.push(b"ZCZC-".map(|byte| byte.into()).to_vec());
-        header.push(self.originator_code.to_afsk_bytes().to_vec());
-        header.push(b"-".map(|byte| byte.into()).to_vec());
-        header.push(self.event_code.map(|byte| byte.into()).to_vec());
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"ZCZC-");
+        bytes.extend_from_slice(&<[u8; 3]>::from(self.originator_code));
+        bytes.push(b'-');
+        bytes.extend_from_slice(&<[u8; 3]>::from(self.event_code));
         for location_code in &self.location_codes {
-            header.push(b"-".map(|byte| byte.into()).to_vec());
-            header.push(location_code.map(|byte| byte.into()).to_vec());
+            bytes.push(b'-');
+            bytes.extend_from_slice(&location_code.to_bytes());
         }
-        header.push(b"+".map(|byte| byte.into()).to_vec());
-        header.push(self.purge_time.map(|byte| byte.into()).to_vec());
-        header.push(b"-".map(|byte| byte.into()).to_vec());
-        header.push(
-            formatted_datetime
-                .as_bytes()
-                .iter()
-                .cloned()
-                .map(|b| b.into())
-                .collect(),
+        bytes.push(b'+');
+        bytes.extend_from_slice(&self.purge_time);
+        bytes.push(b'-');
+        bytes.extend_from_slice(formatted_datetime.as_bytes());
+        bytes.push(b'-');
+        bytes.extend_from_slice(&stripped_callsign);
+        bytes.push(b'-');
+
+        bytes
+    }
+
+    fn render(&self) -> Vec<AfskByte> {
+        let mut header = preamble().to_vec();
+        header.extend(
+            self.same_string_bytes()
+                .into_iter()
+                .map(AfskByte::from),
         );
-        header.push(b"-".map(|byte| byte.into()).to_vec());
-        header.push(stripped_callsign.map(|byte| byte.into()).to_vec());
-        header.push(b"-".map(|byte| byte.into()).to_vec());
+        header
+    }
+}
+
+/// A standard three-letter SAME event code, with a fallback for codes not
+/// covered here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventCode {
+    /// Tornado Warning
+    Tor,
+    /// Severe Thunderstorm Warning
+    Svr,
+    /// Severe Weather Statement
+    Svs,
+    /// Flash Flood Warning
+    Ffw,
+    /// Flash Flood Watch
+    Ffa,
+    /// Emergency Action Notification
+    Ean,
+    /// Required Weekly Test
+    Rwt,
+    /// Required Monthly Test
+    Rmt,
+    /// Civil Emergency Message
+    Cem,
+    /// Evacuation Immediate
+    Evi,
+    /// Winter Storm Warning
+    Wsw,
+    /// Any event code not covered above.
+    Other([u8; 3]),
+}
+
+impl From<EventCode> for [u8; 3] {
+    fn from(code: EventCode) -> Self {
+        match code {
+            EventCode::Tor => *b"TOR",
+            EventCode::Svr => *b"SVR",
+            EventCode::Svs => *b"SVS",
+            EventCode::Ffw => *b"FFW",
+            EventCode::Ffa => *b"FFA",
+            EventCode::Ean => *b"EAN",
+            EventCode::Rwt => *b"RWT",
+            EventCode::Rmt => *b"RMT",
+            EventCode::Cem => *b"CEM",
+            EventCode::Evi => *b"EVI",
+            EventCode::Wsw => *b"WSW",
+            EventCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<EventCode> for [AfskByte; 3] {
+    fn from(code: EventCode) -> Self {
+        <[u8; 3]>::from(code).map(|byte| byte.into())
+    }
+}
 
-        header.concat()
+impl From<[u8; 3]> for EventCode {
+    fn from(code: [u8; 3]) -> Self {
+        match &code {
+            b"TOR" => Self::Tor,
+            b"SVR" => Self::Svr,
+            b"SVS" => Self::Svs,
+            b"FFW" => Self::Ffw,
+            b"FFA" => Self::Ffa,
+            b"EAN" => Self::Ean,
+            b"RWT" => Self::Rwt,
+            b"RMT" => Self::Rmt,
+            b"CEM" => Self::Cem,
+            b"EVI" => Self::Evi,
+            b"WSW" => Self::Wsw,
+            _ => Self::Other(code),
+        }
+    }
+}
+
+/// Errors rejecting a [`LocationCode`] or the list of them attached to a
+/// [`Header`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocationCodeError {
+    /// A byte in the 6-character code was not an ASCII digit.
+    NonDigit(u8),
+    /// The 2-digit state FIPS field did not match a state (01-56) or
+    /// outlying territory (60, 66, 69, 72, 74, 78) FIPS code.
+    StateFipsOutOfRange(u8),
+    /// More than 31 location codes were attached to one header.
+    TooManyCodes,
+}
+
+/// US state (01-56) and outlying-territory FIPS codes valid in a PSSCCC
+/// location code's state field: American Samoa (60), Guam (66), the
+/// Northern Mariana Islands (69), Puerto Rico (72), the US Minor Outlying
+/// Islands (74), and the US Virgin Islands (78).
+const VALID_STATE_FIPS_TERRITORIES: [u8; 6] = [60, 66, 69, 72, 74, 78];
+
+/// A location code attached to a [`Header`].
+///
+/// In the US, this follows the PSSCCC layout: a part-of-county digit, a
+/// 2-digit state FIPS code, and a 3-digit county FIPS code. In Canada, this
+/// is an opaque Canadian Location Code (CLC).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocationCode {
+    /// US PSSCCC-format location code.
+    Pssccc {
+        part_of_county: u8,
+        state_fips: u8,
+        county_fips: u16,
+    },
+    /// Canadian Location Code.
+    Clc([u8; 6]),
+}
+
+impl LocationCode {
+    /// Parse a US PSSCCC-format code from its 6-digit ASCII representation.
+    pub fn pssccc(code: [u8; 6]) -> Result<Self, LocationCodeError> {
+        let digits = Self::digits(code)?;
+        let state_fips = digits[1] * 10 + digits[2];
+        let in_range =
+            (1..=56).contains(&state_fips) || VALID_STATE_FIPS_TERRITORIES.contains(&state_fips);
+        if !in_range {
+            return Err(LocationCodeError::StateFipsOutOfRange(state_fips));
+        }
+        Ok(Self::Pssccc {
+            part_of_county: digits[0],
+            state_fips,
+            county_fips: digits[3] as u16 * 100 + digits[4] as u16 * 10 + digits[5] as u16,
+        })
+    }
+
+    /// Parse a Canadian CLC-format code from its 6-digit ASCII representation.
+    pub fn clc(code: [u8; 6]) -> Result<Self, LocationCodeError> {
+        Self::digits(code)?;
+        Ok(Self::Clc(code))
+    }
+
+    fn digits(code: [u8; 6]) -> Result<[u8; 6], LocationCodeError> {
+        let mut digits = [0u8; 6];
+        for (digit, byte) in digits.iter_mut().zip(code) {
+            if !byte.is_ascii_digit() {
+                return Err(LocationCodeError::NonDigit(byte));
+            }
+            *digit = byte - b'0';
+        }
+        Ok(digits)
+    }
+
+    fn to_bytes(self) -> [u8; 6] {
+        match self {
+            Self::Pssccc {
+                part_of_county,
+                state_fips,
+                county_fips,
+            } => [
+                b'0' + part_of_county,
+                b'0' + state_fips / 10,
+                b'0' + state_fips % 10,
+                b'0' + (county_fips / 100) as u8,
+                b'0' + (county_fips / 10 % 10) as u8,
+                b'0' + (county_fips % 10) as u8,
+            ],
+            Self::Clc(code) => code,
+        }
     }
 }
 
@@ -152,26 +366,44 @@ pub enum OriginatorCode {
     Ean,
 }
 
-impl OriginatorCode {
-    fn to_afsk_bytes(self) -> [AfskByte; 3] {
-        self.into()
+impl From<OriginatorCode> for [u8; 3] {
+    fn from(org: OriginatorCode) -> Self {
+        match org {
+            OriginatorCode::Pep => *b"PEP",
+            OriginatorCode::Civ => *b"CIV",
+            OriginatorCode::Wxr => *b"WXR",
+            OriginatorCode::Eas => *b"WAS",
+            #[allow(
+                deprecated,
+                reason = "Deprecated location code still needs to be implemented."
+            )]
+            OriginatorCode::Ean => *b"EAN",
+        }
     }
 }
 
 impl From<OriginatorCode> for [AfskByte; 3] {
     fn from(org: OriginatorCode) -> Self {
-        match org {
-            OriginatorCode::Pep => b"PEP",
-            OriginatorCode::Civ => b"CIV",
-            OriginatorCode::Wxr => b"WXR",
-            OriginatorCode::Eas => b"WAS",
+        <[u8; 3]>::from(org).map(|byte| byte.into())
+    }
+}
+
+impl TryFrom<[u8; 3]> for OriginatorCode {
+    type Error = [u8; 3];
+
+    fn try_from(code: [u8; 3]) -> Result<Self, Self::Error> {
+        match &code {
+            b"PEP" => Ok(Self::Pep),
+            b"CIV" => Ok(Self::Civ),
+            b"WXR" => Ok(Self::Wxr),
+            b"WAS" => Ok(Self::Eas),
             #[allow(
                 deprecated,
                 reason = "Deprecated location code still needs to be implemented."
             )]
-            OriginatorCode::Ean => b"EAN",
+            b"EAN" => Ok(Self::Ean),
+            _ => Err(code),
         }
-        .map(|byte| byte.into())
     }
 }
 
@@ -262,7 +494,16 @@ impl From<u8> for AfskByte {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+impl From<AfskByte> for u8 {
+    fn from(byte: AfskByte) -> Self {
+        byte.bits
+            .iter()
+            .enumerate()
+            .fold(0u8, |acc, (i, bit)| acc | ((*bit == AfskBit::Mark) as u8) << i)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 enum AfskBit {
     Mark,
     #[default]
@@ -278,10 +519,10 @@ impl From<bool> for AfskBit {
 impl From<AfskBit> for MultiSineWave {
     fn from(bit: AfskBit) -> Self {
         let cycles = match bit {
-            AfskBit::Mark => 4.0,
-            AfskBit::Space => 3.0,
+            AfskBit::Mark => MARK_CYCLES,
+            AfskBit::Space => SPACE_CYCLES,
         };
-        Self::single_from_cycles_and_seconds(cycles, 1.92 / 1000.0)
+        Self::single_from_cycles_and_seconds(cycles, BIT_SECONDS)
     }
 }
 struct MultiSineWave {
@@ -322,10 +563,43 @@ impl MultiSineWave {
 
 #[cfg(test)]
 mod test {
+    use std::fs::File;
+
     use chrono::Utc;
-    use hound::{WavSpec, WavWriter};
 
-    use crate::{EasWarning, Header, MultiSineWave, OriginatorCode};
+    use crate::{
+        EasWarning, EventCode, Header, LocationCode, LocationCodeError, MultiSineWave,
+        OriginatorCode,
+        output::{self, OutputFormat},
+    };
+
+    #[test]
+    fn pssccc_rejects_out_of_range_state_fips() {
+        assert_eq!(
+            LocationCode::pssccc(*b"099077"),
+            Err(LocationCodeError::StateFipsOutOfRange(99))
+        );
+        assert_eq!(
+            LocationCode::pssccc(*b"000077"),
+            Err(LocationCodeError::StateFipsOutOfRange(0))
+        );
+        assert!(LocationCode::pssccc(*b"048077").is_ok());
+    }
+
+    #[test]
+    fn pssccc_accepts_territory_state_fips() {
+        // Puerto Rico (72) is a real SAME/EAS state FIPS code, not a US
+        // state, and must round-trip through `pssccc` rather than being
+        // rejected as out-of-range.
+        assert_eq!(
+            LocationCode::pssccc(*b"072001"),
+            Ok(LocationCode::Pssccc {
+                part_of_county: 0,
+                state_fips: 72,
+                county_fips: 1,
+            })
+        );
+    }
 
     #[test]
     fn simple_sine() {
@@ -342,17 +616,8 @@ mod test {
     #[ignore]
     fn output_wav() {
         let eas = generate_eas();
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate: 44100,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-        let mut writer = WavWriter::create("data/output.wav", spec).unwrap();
-        for sample in eas {
-            writer.write_sample(sample).unwrap();
-        }
-        writer.finalize().unwrap()
+        let file = File::create("data/output.wav").unwrap();
+        output::write_warning(&eas, 44100, OutputFormat::Wav, file).unwrap();
     }
 
     fn generate_eas() -> Vec<f32> {
@@ -365,10 +630,10 @@ mod test {
 
         let header = Header::builder()
             .time_of_issue(Utc::now())
-            .event_code(*b"IFW")
+            .event_code(EventCode::from(*b"IFW"))
             .purge_time(*b"0015")
             .callsign(*b"EC/GC/CA")
-            .location_codes(vec![*b"082620"])
+            .location_codes(vec![LocationCode::pssccc(*b"082620").unwrap()])
             .unwrap()
             .originator_code(OriginatorCode::Civ)
             .build();
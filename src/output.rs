@@ -0,0 +1,150 @@
+//! Serializing constructed warning audio to real output file formats,
+//! instead of only returning `Vec<f32>` samples.
+//!
+//! EAS/SAME audio is narrowband two-tone AFSK that compresses extremely well
+//! losslessly, so [`OutputFormat::Flac`] (behind the `flac` feature)
+//! dramatically shrinks archived alert recordings while staying bit-exact,
+//! which matters for compliance logging.
+
+use std::io::{self, Write};
+
+/// An output container format [`write_warning`] can encode samples into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 16-bit PCM WAV.
+    Wav,
+    /// Lossless FLAC. Requires the `flac` feature.
+    #[cfg(feature = "flac")]
+    Flac,
+}
+
+/// Errors that can occur while encoding a warning to an [`OutputFormat`].
+#[derive(Debug)]
+pub enum OutputError {
+    /// Writing the encoded output to the writer failed.
+    Io(io::Error),
+    /// The FLAC encoder failed.
+    #[cfg(feature = "flac")]
+    Flac(String),
+}
+
+impl std::fmt::Display for OutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "i/o error writing warning audio: {err}"),
+            #[cfg(feature = "flac")]
+            Self::Flac(err) => write!(f, "FLAC encoder error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for OutputError {}
+
+impl From<io::Error> for OutputError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Encode `samples` (captured at `sample_rate` Hz, mono) as `format` and
+/// write the result to `writer`.
+pub fn write_warning<W: Write>(
+    samples: &[f32],
+    sample_rate: usize,
+    format: OutputFormat,
+    writer: W,
+) -> Result<(), OutputError> {
+    match format {
+        OutputFormat::Wav => write_wav(samples, sample_rate, writer),
+        #[cfg(feature = "flac")]
+        OutputFormat::Flac => write_flac(samples, sample_rate, writer),
+    }
+}
+
+fn to_pcm16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Write `samples` as a canonical 16-bit PCM WAV file.
+///
+/// The total sample count is known up front, so the header's chunk sizes
+/// are computed directly rather than patched in afterwards; this only needs
+/// `W: Write`, not `Write + Seek`.
+fn write_wav<W: Write>(
+    samples: &[f32],
+    sample_rate: usize,
+    mut writer: W,
+) -> Result<(), OutputError> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    const CHANNELS: u16 = 1;
+
+    let block_align = CHANNELS * (BITS_PER_SAMPLE / 8);
+    let byte_rate = sample_rate as u32 * block_align as u32;
+    let data_len = samples.len() as u32 * (BITS_PER_SAMPLE / 8) as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&CHANNELS.to_le_bytes())?;
+    writer.write_all(&(sample_rate as u32).to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    for &sample in samples {
+        writer.write_all(&to_pcm16(sample).to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "flac")]
+fn write_flac<W: Write>(
+    samples: &[f32],
+    sample_rate: usize,
+    mut writer: W,
+) -> Result<(), OutputError> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let pcm_samples: Vec<i32> = samples.iter().map(|&sample| to_pcm16(sample) as i32).collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, err)| OutputError::Flac(err.to_string()))?;
+    let source = flacenc::source::MemSource::from_samples(&pcm_samples, 1, 16, sample_rate);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|err| OutputError::Flac(format!("{err:?}")))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|err| OutputError::Flac(format!("{err:?}")))?;
+
+    writer.write_all(sink.as_slice())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OutputFormat, write_warning};
+
+    #[test]
+    fn wav_header_is_well_formed() {
+        let samples = vec![0.0, 0.5, -0.5, 1.0, -1.0];
+        let mut buffer = Vec::new();
+        write_warning(&samples, 44_100, OutputFormat::Wav, &mut buffer).unwrap();
+
+        assert_eq!(&buffer[0..4], b"RIFF");
+        assert_eq!(&buffer[8..12], b"WAVE");
+        assert_eq!(&buffer[12..16], b"fmt ");
+        assert_eq!(&buffer[36..40], b"data");
+        assert_eq!(buffer.len(), 44 + samples.len() * 2);
+    }
+}
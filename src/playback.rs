@@ -0,0 +1,151 @@
+//! Live playback of a constructed warning through the host's default audio
+//! output device. Requires the `playback` feature.
+//!
+//! This drives a transmitter or soundcard directly, skipping the
+//! intermediate file that writing samples out via
+//! [`crate::output::write_warning`] otherwise requires.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+};
+
+use cpal::{
+    SampleFormat, Stream, StreamConfig,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+};
+
+use crate::EasWarning;
+
+/// Errors that can occur while opening or driving a live playback stream.
+#[derive(Debug)]
+pub enum PlaybackError {
+    /// No default output device was reported by the host.
+    NoOutputDevice,
+    /// The device's default output configuration could not be queried.
+    DefaultStreamConfig(cpal::DefaultStreamConfigError),
+    /// The device does not output in a format this crate knows how to fill.
+    UnsupportedSampleFormat(SampleFormat),
+    /// The output stream could not be built.
+    BuildStream(cpal::BuildStreamError),
+    /// The output stream could not be started.
+    PlayStream(cpal::PlayStreamError),
+}
+
+impl std::fmt::Display for PlaybackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoOutputDevice => write!(f, "no default output device"),
+            Self::DefaultStreamConfig(err) => write!(f, "could not query output config: {err}"),
+            Self::UnsupportedSampleFormat(format) => {
+                write!(f, "unsupported output sample format: {format}")
+            }
+            Self::BuildStream(err) => write!(f, "could not build output stream: {err}"),
+            Self::PlayStream(err) => write!(f, "could not start output stream: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PlaybackError {}
+
+struct PlaybackState {
+    ring: Mutex<VecDeque<f32>>,
+    done: Mutex<bool>,
+    done_cv: Condvar,
+}
+
+impl PlaybackState {
+    fn mark_done(&self) {
+        *self.done.lock().unwrap() = true;
+        self.done_cv.notify_all();
+    }
+}
+
+/// A handle to an in-progress live playback of a warning.
+///
+/// Dropping the handle stops the underlying stream.
+pub struct PlayHandle {
+    stream: Stream,
+    state: Arc<PlaybackState>,
+}
+
+impl PlayHandle {
+    /// Block the calling thread until playback has finished.
+    pub fn wait(&self) {
+        let done = self.state.done.lock().unwrap();
+        let _done = self
+            .state
+            .done_cv
+            .wait_while(done, |done| !*done)
+            .unwrap();
+    }
+
+    /// Stop playback early.
+    pub fn stop(&self) {
+        let _ = self.stream.pause();
+        self.state.mark_done();
+    }
+}
+
+impl EasWarning {
+    /// Render this warning and play it live through the host's default
+    /// output device.
+    ///
+    /// Samples are rendered at the device's own sample rate, up-mixed from
+    /// mono to however many channels the output stream reports, and fed to
+    /// the audio callback through a ring buffer. Requires the `playback`
+    /// feature.
+    pub fn play(
+        &self,
+        message: Option<Vec<f32>>,
+        critical: bool,
+    ) -> Result<PlayHandle, PlaybackError> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or(PlaybackError::NoOutputDevice)?;
+        let supported_config = device
+            .default_output_config()
+            .map_err(PlaybackError::DefaultStreamConfig)?;
+
+        let sample_format = supported_config.sample_format();
+        if sample_format != SampleFormat::F32 {
+            return Err(PlaybackError::UnsupportedSampleFormat(sample_format));
+        }
+
+        let sample_rate = supported_config.sample_rate().0 as usize;
+        let channels = supported_config.channels() as usize;
+        let config: StreamConfig = supported_config.config();
+
+        let samples = self.construct(sample_rate, message, critical);
+
+        let state = Arc::new(PlaybackState {
+            ring: Mutex::new(samples.into()),
+            done: Mutex::new(false),
+            done_cv: Condvar::new(),
+        });
+
+        let callback_state = Arc::clone(&state);
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |output: &mut [f32], _| {
+                    let mut ring = callback_state.ring.lock().unwrap();
+                    for frame in output.chunks_mut(channels) {
+                        let sample = ring.pop_front().unwrap_or(0.0);
+                        frame.fill(sample);
+                    }
+                    if ring.is_empty() {
+                        callback_state.mark_done();
+                    }
+                },
+                |err| eprintln!("playback stream error: {err}"),
+                None,
+            )
+            .map_err(PlaybackError::BuildStream)?;
+
+        stream.play().map_err(PlaybackError::PlayStream)?;
+
+        Ok(PlayHandle { stream, state })
+    }
+}
@@ -0,0 +1,159 @@
+//! A textual layer parallel to the AFSK audio layer: converting a [`Header`]
+//! to and from the canonical `ZCZC-…` SAME/EAS protocol string, with no
+//! audio involved.
+//!
+//! This lets callers interoperate with the many existing SAME tools that
+//! exchange the header as ASCII, validate headers without synthesizing
+//! audio, and build round-trip tests against [`crate::decode`].
+
+use crate::{
+    Header,
+    grammar::{self, FieldError, FramingError},
+};
+
+/// Errors parsing a `ZCZC-…` SAME/EAS protocol string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// A delimiter or fixed-width field was missing or malformed.
+    BadFraming,
+    /// More than 31 location codes were present.
+    TooManyLocationCodes,
+    /// The 3-letter originator code did not match any known
+    /// [`crate::OriginatorCode`].
+    UnknownOriginatorCode([u8; 3]),
+    /// A location code was not a valid 6-digit PSSCCC code.
+    InvalidLocationCode(crate::LocationCodeError),
+    /// The day-of-year/hour/minute field did not form a valid date.
+    InvalidTimeOfIssue,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadFraming => write!(f, "malformed ZCZC- header framing"),
+            Self::TooManyLocationCodes => write!(f, "more than 31 location codes in header"),
+            Self::UnknownOriginatorCode(code) => write!(f, "unknown originator code {code:?}"),
+            Self::InvalidLocationCode(err) => write!(f, "invalid location code: {err:?}"),
+            Self::InvalidTimeOfIssue => write!(f, "invalid day-of-year/hour/minute field"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Errors serializing a [`Header`] as a `ZCZC-…` protocol string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// A `callsign` byte or `EventCode::Other` byte was not ASCII, even
+    /// though the protocol string is ASCII-only. Holds the first such byte.
+    NonAscii(u8),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonAscii(byte) => write!(f, "header byte {byte:#04x} is not valid ASCII"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<FramingError> for ParseError {
+    fn from(err: FramingError) -> Self {
+        match err {
+            FramingError::BadFraming => Self::BadFraming,
+            FramingError::TooManyLocationCodes => Self::TooManyLocationCodes,
+        }
+    }
+}
+
+impl From<FieldError> for ParseError {
+    fn from(err: FieldError) -> Self {
+        match err {
+            FieldError::UnknownOriginatorCode(code) => Self::UnknownOriginatorCode(code),
+            FieldError::InvalidLocationCode(err) => Self::InvalidLocationCode(err),
+            FieldError::InvalidTimeOfIssue => Self::InvalidTimeOfIssue,
+        }
+    }
+}
+
+impl Header {
+    /// Serialize this header as the canonical
+    /// `ZCZC-ORG-EEE-PSSCCC-PSSCCC…+TTTT-JJJHHMM-CALLSIGN-` protocol string,
+    /// with no AFSK modulation.
+    ///
+    /// Fails with [`RenderError::NonAscii`] if `callsign` or an
+    /// [`crate::EventCode::Other`] byte is not ASCII; nothing in the builder
+    /// rejects those at construction time.
+    pub fn to_same_string(&self) -> Result<String, RenderError> {
+        let bytes = self.same_string_bytes();
+        if let Some(&byte) = bytes.iter().find(|byte| !byte.is_ascii()) {
+            return Err(RenderError::NonAscii(byte));
+        }
+        Ok(String::from_utf8(bytes).expect("ASCII bytes are valid UTF-8"))
+    }
+
+    /// Parse a `ZCZC-…` SAME/EAS protocol string back into a [`Header`].
+    ///
+    /// Location codes are parsed as [`crate::LocationCode::Pssccc`], since
+    /// the string does not distinguish US PSSCCC codes from Canadian CLC
+    /// codes. The time of issue is only encoded as a day-of-year/hour/minute
+    /// triple (no year), so the resulting [`chrono::DateTime`] assumes the
+    /// current UTC year.
+    pub fn from_same_string(same_string: &str) -> Result<Header, ParseError> {
+        let raw = grammar::parse_raw(same_string.as_bytes())?;
+        Ok(grammar::assemble(raw)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+
+    use crate::{EventCode, Header, LocationCode, OriginatorCode};
+
+    use super::RenderError;
+
+    #[test]
+    fn round_trip() {
+        let header = Header::builder()
+            .time_of_issue(Utc::now())
+            .event_code(EventCode::Tor)
+            .purge_time(*b"0030")
+            .callsign(*b"KWWW/TV ")
+            .location_codes(vec![
+                LocationCode::pssccc(*b"048113").unwrap(),
+                LocationCode::pssccc(*b"048121").unwrap(),
+            ])
+            .unwrap()
+            .originator_code(OriginatorCode::Wxr)
+            .build();
+
+        let same_string = header.to_same_string().expect("header bytes are ASCII");
+        let parsed = Header::from_same_string(&same_string).expect("should parse");
+
+        assert_eq!(
+            parsed.to_same_string().expect("header bytes are ASCII"),
+            same_string
+        );
+    }
+
+    #[test]
+    fn to_same_string_rejects_non_ascii_callsign() {
+        let header = Header::builder()
+            .time_of_issue(Utc::now())
+            .event_code(EventCode::Tor)
+            .purge_time(*b"0030")
+            .callsign([0xFF; 8])
+            .location_codes(vec![])
+            .unwrap()
+            .originator_code(OriginatorCode::Wxr)
+            .build();
+
+        assert!(matches!(
+            header.to_same_string(),
+            Err(RenderError::NonAscii(_))
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Transport-agnostic destinations for rendered warning samples.
+//!
+//! [`EasWarning::construct_to`](crate::EasWarning::construct_to) pushes
+//! samples to a [`SampleSink`] as each [`Section`](crate) is rendered,
+//! instead of materializing the whole broadcast into one `Vec<f32>` up
+//! front. This lets the crate feed a software-defined-radio pipeline or a
+//! networked transmitter directly; the message body passed in is still held
+//! in memory whole.
+
+use std::{
+    convert::Infallible,
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+/// A destination for rendered warning samples, pushed in rendering order.
+pub trait SampleSink {
+    /// The error a sink can fail with while accepting samples.
+    type Error;
+
+    /// Accept the next chunk of rendered samples.
+    fn push(&mut self, samples: &[f32]) -> Result<(), Self::Error>;
+}
+
+impl SampleSink for Vec<f32> {
+    type Error = Infallible;
+
+    fn push(&mut self, samples: &[f32]) -> Result<(), Self::Error> {
+        self.extend_from_slice(samples);
+        Ok(())
+    }
+}
+
+/// Streams samples to a [`std::io::Write`] byte stream as interleaved
+/// little-endian 32-bit float PCM.
+pub struct WriteSink<W> {
+    writer: W,
+}
+
+impl<W: Write> WriteSink<W> {
+    /// Wrap an existing writer as a sink.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Recover the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> SampleSink for WriteSink<W> {
+    type Error = io::Error;
+
+    fn push(&mut self, samples: &[f32]) -> Result<(), Self::Error> {
+        for &sample in samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Streams a live alert to a remote TCP listener as interleaved
+/// little-endian 32-bit float PCM.
+pub struct TcpSink {
+    inner: WriteSink<TcpStream>,
+}
+
+impl TcpSink {
+    /// Connect to `addr` and stream samples to it as they're pushed.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            inner: WriteSink::new(TcpStream::connect(addr)?),
+        })
+    }
+}
+
+impl SampleSink for TcpSink {
+    type Error = io::Error;
+
+    fn push(&mut self, samples: &[f32]) -> Result<(), Self::Error> {
+        self.inner.push(samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SampleSink, WriteSink};
+
+    #[test]
+    fn write_sink_interleaves_little_endian_pcm() {
+        let mut sink = WriteSink::new(Vec::new());
+        sink.push(&[1.0, -1.0]).unwrap();
+
+        let bytes = sink.into_inner();
+        assert_eq!(&bytes[0..4], &1.0f32.to_le_bytes());
+        assert_eq!(&bytes[4..8], &(-1.0f32).to_le_bytes());
+    }
+}